@@ -0,0 +1,291 @@
+//! Additional [`Map`] backends beyond the `DashMap`/`parking_lot::RwLock<HashMap>` pair in
+//! `lib.rs`. Adding a backend to the benchmark is: implement [`Map`] for it here (or in its
+//! own module, for anything sizeable), add a constructor function, then add a `MapType`
+//! variant and match arm at each of the three dispatch sites in `main.rs`.
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::ops::Deref;
+use std::sync::RwLock as StdRwLock;
+
+use parking_lot::{Mutex, MutexGuard};
+
+use crate::Map;
+
+/// `shard_count` independent `Mutex`-guarded `HashMap` shards, hashed the same way `DashMap`
+/// shards its internal table. Lets us isolate how much of DashMap's advantage over a single
+/// global lock comes from sharding alone, versus its lock-free reads.
+pub struct ShardedMutexMap<K, V> {
+    shards: Vec<Mutex<HashMap<K, V, RandomState>>>,
+    hash_builder: RandomState,
+}
+
+impl<K: Eq + Hash, V> ShardedMutexMap<K, V> {
+    pub fn new(shard_count: usize) -> Self {
+        let shard_count = shard_count.max(1);
+        Self {
+            shards: (0..shard_count)
+                .map(|_| Mutex::new(HashMap::default()))
+                .collect(),
+            hash_builder: RandomState::new(),
+        }
+    }
+
+    fn shard_index(&self, key: &K) -> usize {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish() as usize % self.shards.len()
+    }
+}
+
+pub fn new_sharded_mutex_fn<K: Eq + Hash, V>(shards: usize) -> impl Fn() -> ShardedMutexMap<K, V> {
+    move || ShardedMutexMap::new(shards)
+}
+
+impl<K: Eq + Hash + Clone, V> Map<K, V> for ShardedMutexMap<K, V> {
+    fn insert(&self, key: K, value: V) {
+        let index = self.shard_index(&key);
+        self.shards[index].lock().insert(key, value);
+    }
+
+    fn get(&self, key: &K) -> Option<impl Deref<Target = V>> {
+        let index = self.shard_index(key);
+        MutexGuard::try_map(self.shards[index].lock(), |shard| shard.get(key)).ok()
+    }
+
+    fn keys(&self) -> Vec<K> {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.lock().keys().cloned().collect::<Vec<_>>())
+            .collect()
+    }
+
+    fn remove(&self, key: &K) -> bool {
+        let index = self.shard_index(key);
+        self.shards[index].lock().remove(key).is_some()
+    }
+
+    fn update(&self, key: &K, f: impl FnOnce(&mut V)) {
+        let index = self.shard_index(key);
+        if let Some(value) = self.shards[index].lock().get_mut(key) {
+            f(value);
+        }
+    }
+
+    fn upsert(&self, key: K, default: impl FnOnce() -> V, f: impl FnOnce(&mut V)) {
+        let index = self.shard_index(&key);
+        self.shards[index]
+            .lock()
+            .entry(key)
+            .and_modify(f)
+            .or_insert_with(default);
+    }
+}
+
+pub fn new_std_rwlock_hashmap<K: Eq + Hash, V>() -> StdRwLock<HashMap<K, V>> {
+    StdRwLock::new(HashMap::new())
+}
+
+/// `std::sync::RwLockReadGuard` can't be mapped to a sub-borrow on stable (unlike
+/// `parking_lot`'s), so `get` holds the whole-map guard alongside a cloned key and derefs
+/// through a second lookup instead.
+struct StdRwLockEntryGuard<'a, K, V> {
+    guard: std::sync::RwLockReadGuard<'a, HashMap<K, V>>,
+    key: K,
+}
+
+impl<'a, K: Eq + Hash, V> Deref for StdRwLockEntryGuard<'a, K, V> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        self.guard
+            .get(&self.key)
+            .expect("key present when guard was created")
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> Map<K, V> for StdRwLock<HashMap<K, V>> {
+    fn insert(&self, key: K, value: V) {
+        self.write().unwrap().insert(key, value);
+    }
+
+    fn get(&self, key: &K) -> Option<impl Deref<Target = V>> {
+        let guard = self.read().unwrap();
+        guard.contains_key(key).then(|| StdRwLockEntryGuard {
+            guard,
+            key: key.clone(),
+        })
+    }
+
+    fn keys(&self) -> Vec<K> {
+        self.read().unwrap().keys().cloned().collect()
+    }
+
+    fn remove(&self, key: &K) -> bool {
+        self.write().unwrap().remove(key).is_some()
+    }
+
+    fn update(&self, key: &K, f: impl FnOnce(&mut V)) {
+        if let Some(value) = self.write().unwrap().get_mut(key) {
+            f(value);
+        }
+    }
+
+    fn upsert(&self, key: K, default: impl FnOnce() -> V, f: impl FnOnce(&mut V)) {
+        self.write()
+            .unwrap()
+            .entry(key)
+            .and_modify(f)
+            .or_insert_with(default);
+    }
+}
+
+/// Stub backed by `flurry`'s lock-free hash map. Gated behind the `flurry` feature since it
+/// pulls in a crossbeam-epoch dependency that most users of this benchmark won't need.
+/// flurry's references are scoped to a `pin()` guard, so `get` can't hand one back through
+/// [`Map`]; `get` (for a present key), `update` and `upsert` are unimplemented and panic.
+#[cfg(feature = "flurry")]
+pub mod flurry_backend {
+    use std::hash::Hash;
+    use std::ops::Deref;
+
+    use crate::Map;
+
+    pub struct FlurryMap<K, V>(flurry::HashMap<K, V>);
+
+    impl<K: Eq + Hash + Send + Sync + 'static, V: Send + Sync + 'static> FlurryMap<K, V> {
+        pub fn new() -> Self {
+            Self(flurry::HashMap::new())
+        }
+    }
+
+    impl<K: Eq + Hash + Clone + Send + Sync + 'static, V: Send + Sync + 'static> Map<K, V>
+        for FlurryMap<K, V>
+    {
+        fn insert(&self, key: K, value: V) {
+            self.0.pin().insert(key, value);
+        }
+
+        fn get(&self, key: &K) -> Option<impl Deref<Target = V>> {
+            // flurry's guard-scoped references don't outlive the `pin()` call, so this
+            // backend can't satisfy Map::get's `Option<impl Deref>` shape; `get` is listed as
+            // unsupported for this backend in `MapType::unsupported_ops` and validated
+            // against before a run starts. The `else` arm only exists to give the opaque
+            // return type a concrete witness (`Arc<V>`) so this compiles as the stub it is.
+            if self.0.pin().get(key).is_some() {
+                unimplemented!("flurry get is guard-scoped; see module docs")
+            } else {
+                None::<std::sync::Arc<V>>
+            }
+        }
+
+        fn keys(&self) -> Vec<K> {
+            self.0.pin().keys().cloned().collect()
+        }
+
+        fn remove(&self, key: &K) -> bool {
+            self.0.pin().remove(key).is_some()
+        }
+
+        fn update(&self, key: &K, f: impl FnOnce(&mut V)) {
+            let _ = (key, f);
+            unimplemented!("flurry backend does not yet support in-place update")
+        }
+
+        fn upsert(&self, key: K, default: impl FnOnce() -> V, f: impl FnOnce(&mut V)) {
+            let _ = (key, default, f);
+            unimplemented!("flurry backend does not yet support upsert")
+        }
+    }
+}
+
+/// Stub backed by `evmap`'s eventually-consistent reader/writer map. Gated behind the
+/// `evmap` feature. evmap's single-writer, `refresh()`-to-publish model doesn't fit the
+/// shared-`&self` [`Map`] trait as naturally as the others, so writers are serialized behind
+/// a `Mutex<WriteHandle>` and every write is published with `refresh()`. `get`, `update` and
+/// `upsert` are unimplemented and panic.
+#[cfg(feature = "evmap")]
+pub mod evmap_backend {
+    use std::hash::Hash;
+    use std::ops::Deref;
+
+    use crate::Map;
+
+    pub struct EvmapMap<K, V> {
+        read: evmap::ReadHandle<K, V>,
+        write: std::sync::Mutex<evmap::WriteHandle<K, V>>,
+    }
+
+    impl<K, V> EvmapMap<K, V>
+    where
+        K: Eq + Hash + Clone,
+        V: Eq + Hash + Clone,
+    {
+        pub fn new() -> Self {
+            let (read, write) = evmap::new();
+            Self {
+                read,
+                write: std::sync::Mutex::new(write),
+            }
+        }
+    }
+
+    impl<K, V> Map<K, V> for EvmapMap<K, V>
+    where
+        K: Eq + Hash + Clone,
+        V: Eq + Hash + Clone,
+    {
+        fn insert(&self, key: K, value: V) {
+            let mut write = self.write.lock().unwrap();
+            write.update(key, value);
+            write.refresh();
+        }
+
+        fn get(&self, key: &K) -> Option<impl Deref<Target = V>> {
+            // evmap's ReadGuard can't express Map::get's Option<impl Deref> shape; `get` is
+            // listed as unsupported for this backend in `MapType::unsupported_ops` and
+            // validated against before a run starts. The `else` arm only exists to give the
+            // opaque return type a concrete witness (`Arc<V>`) so this compiles as the stub
+            // it is.
+            let present = self
+                .read
+                .read()
+                .map(|m| m.contains_key(key))
+                .unwrap_or(false);
+            if present {
+                unimplemented!(
+                    "evmap's ReadGuard can't express Map::get's Option<impl Deref> shape yet"
+                )
+            } else {
+                None::<std::sync::Arc<V>>
+            }
+        }
+
+        fn keys(&self) -> Vec<K> {
+            self.read
+                .read()
+                .map(|m| m.keys().cloned().collect())
+                .unwrap_or_default()
+        }
+
+        fn remove(&self, key: &K) -> bool {
+            let mut write = self.write.lock().unwrap();
+            let present = write.contains_key(key);
+            write.empty(key.clone());
+            write.refresh();
+            present
+        }
+
+        fn update(&self, _key: &K, _f: impl FnOnce(&mut V)) {
+            unimplemented!(
+                "evmap values are immutable once published; update requires read-modify-insert"
+            )
+        }
+
+        fn upsert(&self, _key: K, _default: impl FnOnce() -> V, _f: impl FnOnce(&mut V)) {
+            unimplemented!(
+                "evmap values are immutable once published; upsert requires read-modify-insert"
+            )
+        }
+    }
+}
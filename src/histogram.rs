@@ -0,0 +1,140 @@
+//! A lightweight log-linear latency histogram, in the spirit of HdrHistogram: recording is
+//! O(1) and allocation-free, at the cost of a few percent of relative error per bucket. This
+//! lets every worker thread time every individual operation without the recording itself
+//! perturbing the benchmark.
+use std::time::Duration;
+
+/// Number of low-order bits tracked linearly within each power-of-two bucket. Higher values
+/// trade memory for precision; 5 bits keeps bucketing error under ~3%.
+const SIGNIFICANT_BITS: u32 = 5;
+const SUBBUCKET_COUNT: usize = 1 << SIGNIFICANT_BITS;
+/// Covers durations up to ~2^40 ns (~18 minutes), comfortably past the ~1ns..~10s range we
+/// expect even a badly-contended lock to produce.
+const MAX_BIT_WIDTH: u32 = 40;
+const BUCKET_COUNT: usize = (MAX_BIT_WIDTH - SIGNIFICANT_BITS + 1) as usize * SUBBUCKET_COUNT;
+
+/// A per-thread (or merged) histogram of operation latencies, recorded in nanoseconds.
+#[derive(Clone)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    count: u64,
+    sum_nanos: u128,
+    max_nanos: u64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; BUCKET_COUNT],
+            count: 0,
+            sum_nanos: 0,
+            max_nanos: 0,
+        }
+    }
+
+    /// Records one latency sample, in nanoseconds.
+    pub fn record(&mut self, nanos: u64) {
+        let nanos = nanos.max(1);
+        let index = Self::bucket_index(nanos).min(self.counts.len() - 1);
+        self.counts[index] += 1;
+        self.count += 1;
+        self.sum_nanos += nanos as u128;
+        self.max_nanos = self.max_nanos.max(nanos);
+    }
+
+    /// Number of samples recorded so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Merges `other`'s samples into `self`.
+    pub fn merge(&mut self, other: &Self) {
+        for (mine, theirs) in self.counts.iter_mut().zip(&other.counts) {
+            *mine += theirs;
+        }
+        self.count += other.count;
+        self.sum_nanos += other.sum_nanos;
+        self.max_nanos = self.max_nanos.max(other.max_nanos);
+    }
+
+    fn bucket_index(nanos: u64) -> usize {
+        let msb = 63 - nanos.leading_zeros();
+        if msb < SIGNIFICANT_BITS {
+            nanos as usize
+        } else {
+            let shift = msb - SIGNIFICANT_BITS;
+            let bucket_base = (shift + 1) as usize * SUBBUCKET_COUNT;
+            let sub = (nanos >> shift) as usize - SUBBUCKET_COUNT;
+            bucket_base + sub
+        }
+    }
+
+    /// The smallest value that would have been recorded into bucket `index`.
+    fn bucket_lower_bound(index: usize) -> u64 {
+        if index < SUBBUCKET_COUNT {
+            index as u64
+        } else {
+            let shift = (index / SUBBUCKET_COUNT) as u32 - 1;
+            let sub = (index % SUBBUCKET_COUNT) as u64;
+            (sub + SUBBUCKET_COUNT as u64) << shift
+        }
+    }
+
+    /// Returns the `p`th percentile latency in nanoseconds (`p` in `0.0..=100.0`).
+    pub fn percentile(&self, p: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let target = (p / 100.0 * self.count as f64).ceil() as u64;
+        let mut cumulative = 0u64;
+        for (index, &bucket_count) in self.counts.iter().enumerate() {
+            cumulative += bucket_count;
+            if cumulative >= target {
+                return Self::bucket_lower_bound(index);
+            }
+        }
+        self.max_nanos
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        let mean_nanos = if self.count == 0 {
+            0
+        } else {
+            (self.sum_nanos / self.count as u128) as u64
+        };
+        LatencySummary {
+            p50: Duration::from_nanos(self.percentile(50.0)),
+            p90: Duration::from_nanos(self.percentile(90.0)),
+            p99: Duration::from_nanos(self.percentile(99.0)),
+            p999: Duration::from_nanos(self.percentile(99.9)),
+            max: Duration::from_nanos(self.max_nanos),
+            mean: Duration::from_nanos(mean_nanos),
+        }
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Percentile/mean/max summary of a [`LatencyHistogram`], ready to print.
+pub struct LatencySummary {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub p999: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+}
+
+impl std::fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "p50={:?} p90={:?} p99={:?} p99.9={:?} max={:?} mean={:?}",
+            self.p50, self.p90, self.p99, self.p999, self.max, self.mean
+        )
+    }
+}
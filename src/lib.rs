@@ -1,15 +1,32 @@
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::io::Write;
-use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Barrier};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{collections::hash_map::RandomState, ops::Deref};
 
 use clap::ValueEnum;
 use dashmap::DashMap;
 use parking_lot::{RwLock, RwLockReadGuard};
 use rand::Rng;
-use rand_distr::{Distribution, Normal};
+use rand_distr::{Distribution, Normal, Zipf};
+
+mod backends;
+mod histogram;
+mod rate_limiter;
+mod sampler;
+mod watchdog;
+pub use backends::{new_sharded_mutex_fn, new_std_rwlock_hashmap, ShardedMutexMap};
+pub use histogram::LatencyHistogram;
+pub use rate_limiter::TokenBucket;
+pub use sampler::{ResourceSampler, ResourceSummary};
+pub use watchdog::Watchdog;
+
+#[cfg(feature = "evmap")]
+pub use backends::evmap_backend::EvmapMap;
+#[cfg(feature = "flurry")]
+pub use backends::flurry_backend::FlurryMap;
 
 pub fn new_dashmap_fn<K: Eq + Hash, V>(shards: usize) -> impl Fn() -> DashMap<K, V> {
     move || DashMap::with_capacity_and_hasher_and_shard_amount(0, RandomState::default(), shards)
@@ -19,43 +36,93 @@ pub fn new_rwlock_hashmap<K: Eq + Hash, V>() -> RwLock<HashMap<K, V>> {
     RwLock::new(HashMap::new())
 }
 
-/// Initializes an outer map and fills it with inner maps
-pub fn test_init_many_maps<OuterMap: Map<u64, InnerMap>, InnerMap: Map<u64, ()>>(
+/// Initializes an outer map and fills it with inner maps, using `writers` threads that pull
+/// indices one at a time from a shared atomic counter so "Init" time isn't bottlenecked on one core.
+pub fn test_init_many_maps<OuterMap, InnerMap>(
     entries: u64,
     ave_inner_items: u64,
+    writers: u64,
     new_outer: impl Fn() -> OuterMap,
-    new_inner: impl Fn() -> InnerMap,
-) {
-    let mut rng = rand::thread_rng();
-    let dist = Normal::new(ave_inner_items as f64, ave_inner_items as f64 / 3.0).unwrap();
-    let drop_start = {
-        let start = SystemTime::now();
-        let dm: OuterMap = new_outer();
-        let mut peak_mem_megs = 0;
-        for i in 0..entries {
-            let inner_map: InnerMap = new_inner();
-            if ave_inner_items != 0 {
-                for x in 0..(dist.sample(&mut rng) as u64) {
-                    inner_map.insert(x, ());
+    new_inner: impl Fn() -> InnerMap + Send + Sync + 'static,
+) where
+    OuterMap: Map<u64, InnerMap> + Send + Sync + 'static,
+    InnerMap: Map<u64, ()> + Send + 'static,
+{
+    let sampler = ResourceSampler::start();
+    let outer = Arc::new(new_outer());
+    let new_inner = Arc::new(new_inner);
+    let next_index = Arc::new(AtomicU64::new(0));
+    let progress = Arc::new(AtomicU64::new(0));
+    // +1 so the main thread can wait alongside the writers and start the clock only once
+    // every writer has spawned and is ready to go, rather than timing in thread spawn overhead.
+    let barrier = Arc::new(Barrier::new(writers as usize + 1));
+
+    let writer_handles: Vec<_> = (0..writers)
+        .map(|_| {
+            let outer = outer.clone();
+            let new_inner = new_inner.clone();
+            let next_index = next_index.clone();
+            let progress = progress.clone();
+            let barrier = barrier.clone();
+            std::thread::spawn(move || {
+                let mut rng = rand::thread_rng();
+                let dist =
+                    Normal::new(ave_inner_items as f64, ave_inner_items as f64 / 3.0).unwrap();
+                barrier.wait();
+                loop {
+                    let i = next_index.fetch_add(1, Ordering::Relaxed);
+                    if i >= entries {
+                        break;
+                    }
+                    let inner_map = new_inner();
+                    if ave_inner_items != 0 {
+                        for x in 0..(dist.sample(&mut rng) as u64) {
+                            inner_map.insert(x, ());
+                        }
+                    }
+                    outer.insert(i, inner_map);
+                    progress.fetch_add(1, Ordering::Relaxed);
                 }
-            }
-            dm.insert(i, inner_map);
-            if i % (entries / 100) == 0 {
+            })
+        })
+        .collect();
+
+    // A dedicated monitor thread polls memory/progress so reporting doesn't serialize the writers.
+    let monitor_handle = {
+        let progress = progress.clone();
+        std::thread::spawn(move || {
+            let mut peak_mem_megs = 0;
+            loop {
+                let done = progress.load(Ordering::Relaxed).min(entries);
                 peak_mem_megs = std::cmp::max(peak_mem_megs, memory_usage().unwrap() / 1_000_000);
-                print!(
-                    "\rallocated {}%  | {} MB",
-                    i / (entries / 100),
-                    peak_mem_megs
-                );
+                let percent = if entries == 0 { 100 } else { done * 100 / entries };
+                print!("\rallocated {percent}%  | {peak_mem_megs} MB");
                 std::io::stdout().flush().unwrap();
+                if done >= entries {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(100));
             }
-        }
-        println!("\rallocated 100%");
-        print_duration(start, "Init");
-        println!("dropping...");
-        SystemTime::now()
+        })
     };
+
+    barrier.wait();
+    let start = SystemTime::now();
+    for handle in writer_handles {
+        handle.join().unwrap();
+    }
+    monitor_handle.join().unwrap();
+    println!("\rallocated 100%");
+    print_duration(start, "Init");
+
+    println!("dropping...");
+    let drop_start = SystemTime::now();
+    drop(outer);
     print_duration(drop_start, "Drop");
+    println!(
+        "resources: {}",
+        ResourceSummary::from_samples(&sampler.stop())
+    );
 }
 
 /// If a focus is selected, that means the other operation will be looped infinitely.
@@ -66,6 +133,10 @@ pub enum ContentionFocus {
     Write,
 }
 
+/// Runs the contention test and returns the achieved aggregate throughput (read + write
+/// ops/sec), for use by callers such as [`test_thread_scaling`] that sweep over thread counts.
+/// A [`Watchdog`] aborts the process with diagnostics if any worker goes `op_timeout` without
+/// completing an operation, so a livelocked `--focus` run hangs loudly instead of silently.
 pub fn test_contention(
     focus: Option<ContentionFocus>,
     range: u64,
@@ -73,14 +144,28 @@ pub fn test_contention(
     writes_per_second: u64,
     reads_per_second: u64,
     cheap_reads: bool,
+    burst: u64,
+    threads_each: u64,
+    op_timeout: Duration,
     map: impl Map<u64, ()> + Send + Sync + 'static,
-) {
+) -> f64 {
     let map = Arc::new(map);
     let mut reader_handles = vec![];
     let mut writer_handles = vec![];
-    let threads_each = usize::from(std::thread::available_parallelism().unwrap()) as u64;
-    let write_gap_nanos = gap_nanos(threads_each, writes_per_second);
-    let read_gap_nanos = gap_nanos(threads_each, reads_per_second);
+    // One bucket per role, shared across all of that role's threads, so the *aggregate* rate
+    // is what's enforced rather than `rate / threads_each` rounded per thread.
+    let write_bucket = (writes_per_second > 0).then(|| {
+        Arc::new(std::sync::Mutex::new(TokenBucket::new(
+            writes_per_second as f64,
+            burst as f64,
+        )))
+    });
+    let read_bucket = (reads_per_second > 0).then(|| {
+        Arc::new(std::sync::Mutex::new(TokenBucket::new(
+            reads_per_second as f64,
+            burst as f64,
+        )))
+    });
 
     // Initialize the map with some data before running the benchmark
     let mut rng = rand::thread_rng();
@@ -88,81 +173,358 @@ pub fn test_contention(
         map.insert(rng.gen_range(0..=range), ());
     }
 
+    // A worker id is assigned per (role, thread) pair in the same order they're spawned
+    // below, so `watchdog.heartbeat(id)` inside each thread lines up with its label here.
+    let mut labels = vec![];
+    for i in 0..threads_each {
+        if write_bucket.is_some() {
+            labels.push(format!("writer-{i}"));
+        }
+        if read_bucket.is_some() {
+            labels.push(format!("reader-{i}"));
+        }
+    }
+    let watchdog = Arc::new(Watchdog::start(labels, op_timeout, {
+        let map = map.clone();
+        move || map.keys().len()
+    }));
+
+    // Each round's total op count is claimed from a counter shared by every thread of the
+    // role, via `fetch_update`, instead of a per-thread `rate / threads_each` floor-divided
+    // loop bound; otherwise the remainder is silently dropped every round (e.g. rate 100 over
+    // 6 threads would floor-divide to 16 each, 96 total, never 100). A `Barrier` resyncs the
+    // role's threads at the start of every round so exactly one of them resets the counter.
+    let write_round = write_bucket.is_some().then(|| {
+        (
+            Arc::new(AtomicU64::new(0)),
+            Arc::new(Barrier::new(threads_each as usize)),
+        )
+    });
+    let read_round = read_bucket.is_some().then(|| {
+        (
+            Arc::new(AtomicU64::new(0)),
+            Arc::new(Barrier::new(threads_each as usize)),
+        )
+    });
+
+    let sampler = ResourceSampler::start();
     let start = SystemTime::now();
+    let mut next_worker_id = 0usize;
     for _ in 0..threads_each {
         // Attempt to write data concurrently for ~1 second at the specified rate (or indefinitely if read is focus)
-        if let Some(write_gap_nanos) = write_gap_nanos {
+        if let Some(write_bucket) = &write_bucket {
+            let worker_id = next_worker_id;
+            next_worker_id += 1;
             let my_map = map.clone();
+            let my_bucket = write_bucket.clone();
+            let my_watchdog = watchdog.clone();
+            let (my_remaining, my_barrier) = write_round.clone().unwrap();
             writer_handles.push(std::thread::spawn(move || {
                 let mut rng = rand::thread_rng();
-                let mut next = unix_timestamp_nanos();
+                let mut latencies = LatencyHistogram::new();
                 loop {
-                    for _ in 0..(writes_per_second / threads_each) {
-                        let now = unix_timestamp_nanos();
-                        if now < next {
-                            std::thread::sleep(Duration::from_nanos((next - now) as u64));
-                        }
+                    if my_barrier.wait().is_leader() {
+                        my_remaining.store(writes_per_second, Ordering::Relaxed);
+                    }
+                    my_barrier.wait();
+                    while my_remaining
+                        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                        .is_ok()
+                    {
+                        TokenBucket::acquire(&my_bucket, 1.0);
+                        let op_start = Instant::now();
                         my_map.insert(rng.gen_range(0..=range), ());
-                        next += write_gap_nanos;
+                        latencies.record(op_start.elapsed().as_nanos() as u64);
+                        my_watchdog.heartbeat(worker_id);
                     }
                     if focus != Some(ContentionFocus::Read) {
                         break;
                     }
                 }
+                my_watchdog.finish(worker_id);
+                latencies
             }));
         }
         // Attempt to read data concurrently for ~1 second at the specified rate (or indefinitely if write is focus)
-        if let Some(read_gap_nanos) = read_gap_nanos {
+        if let Some(read_bucket) = &read_bucket {
+            let worker_id = next_worker_id;
+            next_worker_id += 1;
             let my_map = map.clone();
+            let my_bucket = read_bucket.clone();
+            let my_watchdog = watchdog.clone();
+            let (my_remaining, my_barrier) = read_round.clone().unwrap();
             reader_handles.push(std::thread::spawn(move || {
                 let mut rng = rand::thread_rng();
-                let mut next = unix_timestamp_nanos();
+                let mut latencies = LatencyHistogram::new();
                 loop {
-                    for _ in 0..(reads_per_second / threads_each) {
-                        let now = unix_timestamp_nanos();
-                        if now < next {
-                            std::thread::sleep(Duration::from_nanos((next - now) as u64));
-                        }
+                    if my_barrier.wait().is_leader() {
+                        my_remaining.store(reads_per_second, Ordering::Relaxed);
+                    }
+                    my_barrier.wait();
+                    while my_remaining
+                        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                        .is_ok()
+                    {
+                        TokenBucket::acquire(&my_bucket, 1.0);
+                        let op_start = Instant::now();
                         if cheap_reads {
                             my_map.get(&rng.gen_range(0..=range));
                         } else {
                             my_map.keys();
                         }
-                        next += read_gap_nanos;
+                        latencies.record(op_start.elapsed().as_nanos() as u64);
+                        my_watchdog.heartbeat(worker_id);
                     }
                     if focus != Some(ContentionFocus::Write) {
                         break;
                     }
                 }
+                my_watchdog.finish(worker_id);
+                latencies
             }));
         }
     }
 
     let write_waiter = std::thread::spawn(move || {
+        let mut latencies = LatencyHistogram::new();
         for handle in writer_handles {
-            handle.join().unwrap();
+            latencies.merge(&handle.join().unwrap());
         }
-        print_duration(start, "Contention test (writers)");
+        latencies
     });
     let read_waiter = std::thread::spawn(move || {
+        let mut latencies = LatencyHistogram::new();
         for handle in reader_handles {
-            handle.join().unwrap();
+            latencies.merge(&handle.join().unwrap());
         }
-        print_duration(start, "Contention test (readers)");
+        latencies
     });
+    let mut total_ops = 0;
     if focus != Some(ContentionFocus::Read) {
-        write_waiter.join().unwrap();
+        let latencies = write_waiter.join().unwrap();
+        print_duration(start, "Contention test (writers)");
+        println!("  write latency: {}", latencies.summary());
+        total_ops += latencies.count();
     }
     if focus != Some(ContentionFocus::Write) {
-        read_waiter.join().unwrap();
+        let latencies = read_waiter.join().unwrap();
+        print_duration(start, "Contention test (readers)");
+        println!("  read latency: {}", latencies.summary());
+        total_ops += latencies.count();
     }
+    println!(
+        "resources: {}",
+        ResourceSummary::from_samples(&sampler.stop())
+    );
+    if let Ok(watchdog) = Arc::try_unwrap(watchdog) {
+        watchdog.stop();
+    }
+
+    let elapsed = SystemTime::now()
+        .duration_since(start)
+        .unwrap()
+        .as_secs_f64();
+    total_ops as f64 / elapsed
 }
 
-pub fn gap_nanos(threads: u64, rate_per_second: u64) -> Option<u128> {
-    if rate_per_second == 0 {
-        None
-    } else {
-        Some(threads as u128 * 1_000_000_000 / rate_per_second as u128)
+/// Percentages of each operation kind to draw in [`test_mix`]. Must sum to 100.
+#[derive(Clone, Copy, Debug)]
+pub struct MixWeights {
+    pub read: u8,
+    pub insert: u8,
+    pub update: u8,
+    pub remove: u8,
+    pub upsert: u8,
+}
+
+impl MixWeights {
+    pub fn validate(&self) {
+        let sum = self.read as u32
+            + self.insert as u32
+            + self.update as u32
+            + self.remove as u32
+            + self.upsert as u32;
+        assert_eq!(sum, 100, "mix percentages must sum to 100, got {sum}");
+    }
+
+    /// Picks an operation for a `0..100` dice roll against the cumulative distribution.
+    fn pick(&self, roll: u8) -> MixOp {
+        let mut acc = 0u8;
+        for (pct, op) in [
+            (self.read, MixOp::Read),
+            (self.insert, MixOp::Insert),
+            (self.update, MixOp::Update),
+            (self.remove, MixOp::Remove),
+            (self.upsert, MixOp::Upsert),
+        ] {
+            acc += pct;
+            if roll < acc {
+                return op;
+            }
+        }
+        MixOp::Upsert
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MixOp {
+    Read,
+    Insert,
+    Update,
+    Remove,
+    Upsert,
+}
+
+/// How keys are drawn from the key space during [`test_mix`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum KeyDistribution {
+    /// Every key in the range is equally likely.
+    Uniform,
+    /// Keys are drawn from a Zipfian distribution, so a small set of keys is hot.
+    Zipfian,
+}
+
+enum KeyGenerator {
+    Uniform { range: u64 },
+    Zipfian { dist: Zipf<f64> },
+}
+
+impl KeyGenerator {
+    fn new(distribution: KeyDistribution, range: u64, zipf_exponent: f64) -> Self {
+        match distribution {
+            KeyDistribution::Uniform => Self::Uniform { range },
+            KeyDistribution::Zipfian => Self::Zipfian {
+                // Zipf samples 1..=n; `sample` shifts that down to 0..=range.
+                dist: Zipf::new(range as f64 + 1.0, zipf_exponent).unwrap(),
+            },
+        }
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> u64 {
+        match self {
+            Self::Uniform { range } => rng.gen_range(0..=*range),
+            Self::Zipfian { dist } => dist.sample(rng) as u64 - 1,
+        }
+    }
+}
+
+/// Runs a configurable blend of read/insert/update/remove/upsert operations against `map`,
+/// in the spirit of libcuckoo's universal benchmark, so backends can be compared under
+/// workloads other than pure-read or pure-write. Returns the achieved aggregate throughput
+/// (ops/sec), for use by callers such as [`test_thread_scaling`] that sweep over thread counts.
+pub fn test_mix(
+    weights: MixWeights,
+    range: u64,
+    prefill_fraction: f64,
+    total_ops: u64,
+    key_distribution: KeyDistribution,
+    zipf_exponent: f64,
+    threads: u64,
+    map: impl Map<u64, u64> + Send + Sync + 'static,
+) -> f64 {
+    weights.validate();
+    let map = Arc::new(map);
+
+    let mut rng = rand::thread_rng();
+    let prefill = (range as f64 * prefill_fraction) as u64;
+    for _ in 0..prefill {
+        map.insert(rng.gen_range(0..=range), 0);
+    }
+
+    // Ops are claimed from a counter shared by every thread, via `fetch_update`, rather than
+    // a per-thread `total_ops / threads` floor-divided loop bound; otherwise any `total_ops`
+    // not evenly divisible by `threads` silently drops the remainder every run, the same bug
+    // chunk0-3 fixed for `test_contention`'s rate limiting. This also means `--threads 0`
+    // simply spawns no workers and completes zero ops, instead of panicking on the division.
+    let remaining = Arc::new(AtomicU64::new(total_ops));
+    let counts = Arc::new(std::sync::Mutex::new([0u64; 5]));
+    let mut handles = vec![];
+    let start = SystemTime::now();
+    for _ in 0..threads {
+        let my_map = map.clone();
+        let my_counts = counts.clone();
+        let my_remaining = remaining.clone();
+        handles.push(std::thread::spawn(move || {
+            let mut rng = rand::thread_rng();
+            let keygen = KeyGenerator::new(key_distribution, range, zipf_exponent);
+            let mut local_counts = [0u64; 5];
+            let mut latencies = LatencyHistogram::new();
+            while my_remaining
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                let key = keygen.sample(&mut rng);
+                let op = weights.pick(rng.gen_range(0..100));
+                let op_start = Instant::now();
+                match op {
+                    MixOp::Read => {
+                        my_map.get(&key);
+                    }
+                    MixOp::Insert => my_map.insert(key, rng.gen()),
+                    MixOp::Update => my_map.update(&key, |v| *v = v.wrapping_add(1)),
+                    MixOp::Remove => {
+                        my_map.remove(&key);
+                    }
+                    MixOp::Upsert => my_map.upsert(key, || rng.gen(), |v| *v = v.wrapping_add(1)),
+                }
+                latencies.record(op_start.elapsed().as_nanos() as u64);
+                local_counts[op as usize] += 1;
+            }
+            let mut counts = my_counts.lock().unwrap();
+            for (total, local) in counts.iter_mut().zip(local_counts) {
+                *total += local;
+            }
+            latencies
+        }));
+    }
+    let mut latencies = LatencyHistogram::new();
+    for handle in handles {
+        latencies.merge(&handle.join().unwrap());
+    }
+    print_duration(start, "Mix test");
+
+    let elapsed = SystemTime::now()
+        .duration_since(start)
+        .unwrap()
+        .as_secs_f64();
+    let counts = counts.lock().unwrap();
+    println!("\nper-operation throughput:");
+    for (label, count) in ["read", "insert", "update", "remove", "upsert"]
+        .iter()
+        .zip(counts.iter())
+    {
+        println!(
+            "  {label:<8} {count:>12} ops   {:>14.0} ops/sec",
+            *count as f64 / elapsed
+        );
+    }
+    println!("latency: {}", latencies.summary());
+    // Aggregate throughput from the ops workers actually completed (`counts`), not the
+    // nominal `total_ops` target. Every op claimed from `remaining` is counted, so this equals
+    // `total_ops` whenever at least one worker ran, and is 0 for `--threads 0`.
+    counts.iter().sum::<u64>() as f64 / elapsed
+}
+
+/// Sweeps `test_contention` (or any `run` closure returning achieved ops/sec) over each thread
+/// count in `thread_counts`, printing a table of threads vs. throughput and the scaling factor
+/// relative to the first (typically single-thread) entry.
+pub fn test_thread_scaling(thread_counts: &[u64], mut run: impl FnMut(u64) -> f64) {
+    let mut results = vec![];
+    for &threads in thread_counts {
+        println!("\n=== threads: {threads} ===");
+        let throughput = run(threads);
+        results.push((threads, throughput));
+    }
+
+    let baseline = results.first().map(|&(_, t)| t).unwrap_or(0.0);
+    println!("\nthreads  ops/sec         scaling");
+    for (threads, throughput) in results {
+        let scaling = if baseline > 0.0 {
+            throughput / baseline
+        } else {
+            0.0
+        };
+        println!("{threads:>7}  {throughput:>14.0}  {scaling:>8.2}x");
     }
 }
 
@@ -170,6 +532,12 @@ pub trait Map<K, V> {
     fn insert(&self, key: K, value: V);
     fn get(&self, key: &K) -> Option<impl Deref<Target = V>>;
     fn keys(&self) -> Vec<K>;
+    /// Removes `key`, returning whether it was present.
+    fn remove(&self, key: &K) -> bool;
+    /// Mutates the value at `key` in place, if present. A no-op if `key` is absent.
+    fn update(&self, key: &K, f: impl FnOnce(&mut V));
+    /// Mutates the value at `key` in place if present, otherwise inserts `default()`.
+    fn upsert(&self, key: K, default: impl FnOnce() -> V, f: impl FnOnce(&mut V));
 }
 
 impl<K: Eq + Hash + Clone, V> Map<K, V> for DashMap<K, V> {
@@ -184,6 +552,20 @@ impl<K: Eq + Hash + Clone, V> Map<K, V> for DashMap<K, V> {
     fn keys(&self) -> Vec<K> {
         self.iter().map(|e| e.key().clone()).collect()
     }
+
+    fn remove(&self, key: &K) -> bool {
+        DashMap::remove(self, key).is_some()
+    }
+
+    fn update(&self, key: &K, f: impl FnOnce(&mut V)) {
+        if let Some(mut value) = self.get_mut(key) {
+            f(&mut value);
+        }
+    }
+
+    fn upsert(&self, key: K, default: impl FnOnce() -> V, f: impl FnOnce(&mut V)) {
+        self.entry(key).and_modify(f).or_insert_with(default);
+    }
 }
 
 impl<K: Eq + Hash + Clone, V> Map<K, V> for RwLock<HashMap<K, V>> {
@@ -198,6 +580,23 @@ impl<K: Eq + Hash + Clone, V> Map<K, V> for RwLock<HashMap<K, V>> {
     fn keys(&self) -> Vec<K> {
         self.read().keys().cloned().collect()
     }
+
+    fn remove(&self, key: &K) -> bool {
+        self.write().remove(key).is_some()
+    }
+
+    fn update(&self, key: &K, f: impl FnOnce(&mut V)) {
+        if let Some(value) = self.write().get_mut(key) {
+            f(value);
+        }
+    }
+
+    fn upsert(&self, key: K, default: impl FnOnce() -> V, f: impl FnOnce(&mut V)) {
+        self.write()
+            .entry(key)
+            .and_modify(f)
+            .or_insert_with(default);
+    }
 }
 
 pub fn memory_usage() -> Option<u64> {
@@ -1,7 +1,15 @@
+use std::time::Duration;
+
 use clap::{command, Parser, Subcommand, ValueEnum};
 
+#[cfg(feature = "evmap")]
+use dashmap_benchmark::EvmapMap;
+#[cfg(feature = "flurry")]
+use dashmap_benchmark::FlurryMap;
 use dashmap_benchmark::{
-    new_dashmap_fn, new_rwlock_hashmap, test_contention, test_init_many_maps, ContentionFocus,
+    new_dashmap_fn, new_rwlock_hashmap, new_sharded_mutex_fn, new_std_rwlock_hashmap,
+    test_contention, test_init_many_maps, test_mix, test_thread_scaling, ContentionFocus,
+    KeyDistribution, MixWeights,
 };
 
 fn main() {
@@ -14,17 +22,52 @@ fn main() {
         Test::Init {
             entries,
             inner_items,
-        } => match args.map {
-            MapType::Dashmap => test_init_many_maps(
-                entries,
-                inner_items,
-                new_dashmap_fn(args.dashmap_shards()),
-                new_dashmap_fn(args.dashmap_shards()),
-            ),
-            MapType::Hashmap => {
-                test_init_many_maps(entries, inner_items, new_rwlock_hashmap, new_rwlock_hashmap)
+            writers,
+        } => {
+            let writers = writers.unwrap_or_else(|| default_threads()[0]);
+            match args.map {
+                MapType::Dashmap => test_init_many_maps(
+                    entries,
+                    inner_items,
+                    writers,
+                    new_dashmap_fn(args.dashmap_shards()),
+                    new_dashmap_fn(args.dashmap_shards()),
+                ),
+                MapType::Hashmap => test_init_many_maps(
+                    entries,
+                    inner_items,
+                    writers,
+                    new_rwlock_hashmap,
+                    new_rwlock_hashmap,
+                ),
+                MapType::ShardedMutex => test_init_many_maps(
+                    entries,
+                    inner_items,
+                    writers,
+                    new_sharded_mutex_fn(args.dashmap_shards()),
+                    new_sharded_mutex_fn(args.dashmap_shards()),
+                ),
+                MapType::StdRwlock => test_init_many_maps(
+                    entries,
+                    inner_items,
+                    writers,
+                    new_std_rwlock_hashmap,
+                    new_std_rwlock_hashmap,
+                ),
+                #[cfg(feature = "flurry")]
+                MapType::Flurry => test_init_many_maps(
+                    entries,
+                    inner_items,
+                    writers,
+                    FlurryMap::new,
+                    FlurryMap::new,
+                ),
+                #[cfg(feature = "evmap")]
+                MapType::Evmap => {
+                    test_init_many_maps(entries, inner_items, writers, EvmapMap::new, EvmapMap::new)
+                }
             }
-        },
+        }
         Test::Contention {
             focus,
             max_entries,
@@ -32,26 +75,199 @@ fn main() {
             writes_per_second,
             reads_per_second,
             cheap_reads,
-        } => match args.map {
-            MapType::Dashmap => test_contention(
-                focus,
-                max_entries.unwrap_or(prior_writes + writes_per_second),
-                prior_writes,
-                writes_per_second,
-                reads_per_second,
-                cheap_reads,
-                new_dashmap_fn(args.dashmap_shards())(),
-            ),
-            MapType::Hashmap => test_contention(
-                focus,
-                max_entries.unwrap_or(prior_writes + writes_per_second),
-                prior_writes,
-                writes_per_second,
-                reads_per_second,
-                cheap_reads,
-                new_rwlock_hashmap(),
-            ),
-        },
+            burst,
+            op_timeout,
+            threads,
+        } => {
+            let op_timeout = Duration::from_secs(op_timeout);
+            if cheap_reads {
+                assert!(
+                    !args.map.unsupported_ops().contains(&"get"),
+                    "map {:?} does not support --cheap-reads (its `get` is unimplemented); \
+                     drop --cheap-reads to fall back to `keys`, or choose a different map",
+                    args.map,
+                );
+            }
+            test_thread_scaling(
+                &threads.unwrap_or_else(default_threads),
+                |threads_each| match args.map {
+                    MapType::Dashmap => test_contention(
+                        focus,
+                        max_entries.unwrap_or(prior_writes + writes_per_second),
+                        prior_writes,
+                        writes_per_second,
+                        reads_per_second,
+                        cheap_reads,
+                        burst,
+                        threads_each,
+                        op_timeout,
+                        new_dashmap_fn(args.dashmap_shards())(),
+                    ),
+                    MapType::Hashmap => test_contention(
+                        focus,
+                        max_entries.unwrap_or(prior_writes + writes_per_second),
+                        prior_writes,
+                        writes_per_second,
+                        reads_per_second,
+                        cheap_reads,
+                        burst,
+                        threads_each,
+                        op_timeout,
+                        new_rwlock_hashmap(),
+                    ),
+                    MapType::ShardedMutex => test_contention(
+                        focus,
+                        max_entries.unwrap_or(prior_writes + writes_per_second),
+                        prior_writes,
+                        writes_per_second,
+                        reads_per_second,
+                        cheap_reads,
+                        burst,
+                        threads_each,
+                        op_timeout,
+                        new_sharded_mutex_fn(args.dashmap_shards())(),
+                    ),
+                    MapType::StdRwlock => test_contention(
+                        focus,
+                        max_entries.unwrap_or(prior_writes + writes_per_second),
+                        prior_writes,
+                        writes_per_second,
+                        reads_per_second,
+                        cheap_reads,
+                        burst,
+                        threads_each,
+                        op_timeout,
+                        new_std_rwlock_hashmap(),
+                    ),
+                    #[cfg(feature = "flurry")]
+                    MapType::Flurry => test_contention(
+                        focus,
+                        max_entries.unwrap_or(prior_writes + writes_per_second),
+                        prior_writes,
+                        writes_per_second,
+                        reads_per_second,
+                        cheap_reads,
+                        burst,
+                        threads_each,
+                        op_timeout,
+                        FlurryMap::new(),
+                    ),
+                    #[cfg(feature = "evmap")]
+                    MapType::Evmap => test_contention(
+                        focus,
+                        max_entries.unwrap_or(prior_writes + writes_per_second),
+                        prior_writes,
+                        writes_per_second,
+                        reads_per_second,
+                        cheap_reads,
+                        burst,
+                        threads_each,
+                        op_timeout,
+                        EvmapMap::new(),
+                    ),
+                },
+            )
+        }
+        Test::Mix {
+            range,
+            read,
+            insert,
+            update,
+            remove,
+            upsert,
+            prefill_fraction,
+            total_ops,
+            key_distribution,
+            zipf_exponent,
+            threads,
+        } => {
+            let weights = MixWeights {
+                read,
+                insert,
+                update,
+                remove,
+                upsert,
+            };
+            for (pct, op, flag) in [
+                (read, "get", "read"),
+                (update, "update", "update"),
+                (upsert, "upsert", "upsert"),
+            ] {
+                assert!(
+                    pct == 0 || !args.map.unsupported_ops().contains(&op),
+                    "map {:?} does not support '{op}' (--{flag} {pct}); set --{flag} 0 or \
+                     choose a different map",
+                    args.map,
+                );
+            }
+            test_thread_scaling(
+                &threads.unwrap_or_else(default_threads),
+                |threads| match args.map {
+                    MapType::Dashmap => test_mix(
+                        weights,
+                        range,
+                        prefill_fraction,
+                        total_ops,
+                        key_distribution,
+                        zipf_exponent,
+                        threads,
+                        new_dashmap_fn(args.dashmap_shards())(),
+                    ),
+                    MapType::Hashmap => test_mix(
+                        weights,
+                        range,
+                        prefill_fraction,
+                        total_ops,
+                        key_distribution,
+                        zipf_exponent,
+                        threads,
+                        new_rwlock_hashmap(),
+                    ),
+                    MapType::ShardedMutex => test_mix(
+                        weights,
+                        range,
+                        prefill_fraction,
+                        total_ops,
+                        key_distribution,
+                        zipf_exponent,
+                        threads,
+                        new_sharded_mutex_fn(args.dashmap_shards())(),
+                    ),
+                    MapType::StdRwlock => test_mix(
+                        weights,
+                        range,
+                        prefill_fraction,
+                        total_ops,
+                        key_distribution,
+                        zipf_exponent,
+                        threads,
+                        new_std_rwlock_hashmap(),
+                    ),
+                    #[cfg(feature = "flurry")]
+                    MapType::Flurry => test_mix(
+                        weights,
+                        range,
+                        prefill_fraction,
+                        total_ops,
+                        key_distribution,
+                        zipf_exponent,
+                        threads,
+                        FlurryMap::new(),
+                    ),
+                    #[cfg(feature = "evmap")]
+                    MapType::Evmap => test_mix(
+                        weights,
+                        range,
+                        prefill_fraction,
+                        total_ops,
+                        key_distribution,
+                        zipf_exponent,
+                        threads,
+                        EvmapMap::new(),
+                    ),
+                },
+            )
+        }
     }
 
     println!("\ndone");
@@ -78,6 +294,11 @@ struct Args {
     test: Test,
 }
 
+/// Single-element thread-count sweep defaulting to the number of available cores.
+fn default_threads() -> Vec<u64> {
+    vec![usize::from(std::thread::available_parallelism().unwrap()) as u64]
+}
+
 impl Args {
     fn dashmap_shards(&self) -> usize {
         self.shards.unwrap_or(
@@ -89,10 +310,45 @@ impl Args {
     }
 }
 
+/// Registry of selectable map backends, keyed by the positional `map` argument string `clap`
+/// derives from each variant's name. To add a backend: implement [`dashmap_benchmark::Map`]
+/// for it, add a constructor alongside the others in `backends.rs`, then add a variant here and a match arm at
+/// each of the three dispatch sites above.
 #[derive(Clone, Debug, ValueEnum)]
 enum MapType {
+    /// `dashmap::DashMap`, sharded internally.
     Dashmap,
+    /// A single `parking_lot::RwLock<HashMap>` behind a global lock.
     Hashmap,
+    /// `dashmap_shards` independent `parking_lot::Mutex`-guarded `HashMap` shards, hashed the
+    /// same way `DashMap` shards its table.
+    ShardedMutex,
+    /// A single `std::sync::RwLock<HashMap>`, for comparison against the `parking_lot` one.
+    StdRwlock,
+    /// `flurry`'s lock-free hash map. Requires the `flurry` feature.
+    #[cfg(feature = "flurry")]
+    Flurry,
+    /// `evmap`'s eventually-consistent reader/writer map. Requires the `evmap` feature.
+    #[cfg(feature = "evmap")]
+    Evmap,
+}
+
+impl MapType {
+    /// `Map` operations this backend doesn't actually perform yet: calling them panics via
+    /// `unimplemented!` in `backends.rs` instead of running. Keep this in sync with that file
+    /// so a bad `--map`/workload combination fails fast here with a clear message, rather than
+    /// mid-run.
+    fn unsupported_ops(&self) -> &'static [&'static str] {
+        match self {
+            MapType::Dashmap | MapType::Hashmap | MapType::ShardedMutex | MapType::StdRwlock => {
+                &[]
+            }
+            #[cfg(feature = "flurry")]
+            MapType::Flurry => &["get", "update", "upsert"],
+            #[cfg(feature = "evmap")]
+            MapType::Evmap => &["get", "update", "upsert"],
+        }
+    }
 }
 
 #[derive(Clone, Debug, Subcommand)]
@@ -106,6 +362,11 @@ enum Test {
         /// Number of items to insert into each inner map on average (normally distributed)
         #[arg(short, long, default_value_t = 0)]
         inner_items: u64,
+
+        /// Number of writer threads inserting into the outer map concurrently.
+        /// Default = the number of available cores.
+        #[arg(short, long)]
+        writers: Option<u64>,
     },
 
     /// Using a single map, executes read and write operations at the specified rates.
@@ -138,9 +399,76 @@ enum Test {
         #[arg(short, long, default_value_t = false)]
         cheap_reads: bool,
 
+        /// Maximum burst size (in tokens) of the shared rate limiter for each role.
+        /// Larger values allow a bigger momentary spike above the steady-state rate.
+        #[arg(short, long, default_value_t = 1000)]
+        burst: u64,
+
         /// If a focus is selected, that means the other operation will be looped infinitely.
         /// The test ends as soon as the focused operation completes.
         #[arg(short, long)]
         focus: Option<ContentionFocus>,
+
+        /// Seconds a worker may go without completing an operation before the watchdog aborts
+        /// the run, e.g. because the focused side has livelocked under contention.
+        #[arg(long, default_value_t = 5)]
+        op_timeout: u64,
+
+        /// Comma-separated thread counts to sweep, e.g. `1,2,4,8,16`.
+        /// The test re-runs once per value and reports throughput vs. concurrency.
+        /// Default = the number of available cores, run once.
+        #[arg(long, value_delimiter = ',')]
+        threads: Option<Vec<u64>>,
+    },
+
+    /// Runs a configurable blend of read/insert/update/remove/upsert operations against a
+    /// single map, in the spirit of libcuckoo's universal benchmark.
+    Mix {
+        /// Keys are randomly selected from 0..=range.
+        #[arg(long, default_value_t = 10_000_000)]
+        range: u64,
+
+        /// Percentage of operations that are reads (`get`).
+        #[arg(long, default_value_t = 80)]
+        read: u8,
+
+        /// Percentage of operations that are inserts.
+        #[arg(long, default_value_t = 5)]
+        insert: u8,
+
+        /// Percentage of operations that update an existing entry in place.
+        #[arg(long, default_value_t = 10)]
+        update: u8,
+
+        /// Percentage of operations that remove an entry.
+        #[arg(long, default_value_t = 0)]
+        remove: u8,
+
+        /// Percentage of operations that upsert (update if present, else insert).
+        #[arg(long, default_value_t = 5)]
+        upsert: u8,
+
+        /// Fraction of `range` to prefill the map with before the timed run starts.
+        #[arg(long, default_value_t = 0.5)]
+        prefill_fraction: f64,
+
+        /// Total number of operations to execute. Worker threads claim them one at a time
+        /// from a shared counter, so a faster thread may execute more of them.
+        #[arg(short, long, default_value_t = 10_000_000)]
+        total_ops: u64,
+
+        /// How keys are drawn from the key space.
+        #[arg(long, value_enum, default_value_t = KeyDistribution::Uniform)]
+        key_distribution: KeyDistribution,
+
+        /// Exponent (`s`) of the Zipfian distribution, if selected. Higher means hotter keys.
+        #[arg(long, default_value_t = 1.0)]
+        zipf_exponent: f64,
+
+        /// Comma-separated thread counts to sweep, e.g. `1,2,4,8,16`.
+        /// The test re-runs once per value and reports throughput vs. concurrency.
+        /// Default = the number of available cores, run once.
+        #[arg(long, value_delimiter = ',')]
+        threads: Option<Vec<u64>>,
     },
 }
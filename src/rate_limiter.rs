@@ -0,0 +1,60 @@
+//! A shared token-bucket rate limiter, modeled on the pacing used by speed-limiting
+//! byte-stream libraries. Unlike a per-thread `next += gap` schedule, tokens are shared
+//! across every thread of a role behind a single [`TokenBucket`], so the *aggregate* rate is
+//! enforced instead of drifting per-thread bursts that silently under- or over-shoot the
+//! target throughput.
+use std::sync::Mutex;
+use std::time::Instant;
+
+pub struct TokenBucket {
+    available: f64,
+    capacity: f64,
+    rate: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// `rate` tokens/sec are added over time, up to `capacity` tokens of burst.
+    pub fn new(rate: f64, capacity: f64) -> Self {
+        Self {
+            available: capacity,
+            capacity,
+            rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.available = (self.available + elapsed * self.rate).min(self.capacity);
+    }
+
+    /// Tries to take `tokens` without blocking. On success, consumes them and returns `None`.
+    /// On failure, returns `Some(wait_secs)` for how long the caller should sleep before
+    /// trying again.
+    fn try_acquire(&mut self, tokens: f64) -> Option<f64> {
+        self.refill();
+        if self.available < tokens {
+            Some((tokens - self.available) / self.rate)
+        } else {
+            self.available -= tokens;
+            None
+        }
+    }
+
+    /// Blocks the calling thread, if necessary, until `tokens` are available from the shared
+    /// `bucket`, then consumes them. The bucket is only ever locked to check and update the
+    /// token count, never while sleeping, so one thread backing off under backpressure doesn't
+    /// serialize the rest of its role behind this lock.
+    pub fn acquire(bucket: &Mutex<TokenBucket>, tokens: f64) {
+        loop {
+            let wait = bucket.lock().unwrap().try_acquire(tokens);
+            match wait {
+                None => return,
+                Some(wait_secs) => std::thread::sleep(std::time::Duration::from_secs_f64(wait_secs)),
+            }
+        }
+    }
+}
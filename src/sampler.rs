@@ -0,0 +1,232 @@
+//! Background CPU/memory sampler, so a run can report a timeline instead of a single
+//! end-of-test snapshot. This is what reveals, for example, whether a lock-based map is
+//! burning CPU spinning rather than doing useful work.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// One CPU/memory reading. `cpu_percent` is this process's total (user+system) CPU usage as
+/// reported by `sysinfo`. `cpu_user_percent`/`cpu_system_percent` split that total into user
+/// vs. system time, which is what actually distinguishes "doing useful work" from "spinning
+/// in the kernel on a contended lock"; `sysinfo` doesn't expose that split at the process
+/// level, so it's read directly from `/proc/[pid]/stat` on Linux and left `None` elsewhere.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub cpu_percent: f32,
+    pub cpu_user_percent: Option<f32>,
+    pub cpu_system_percent: Option<f32>,
+    pub memory_bytes: u64,
+}
+
+/// Linux reports utime/stime in clock ticks, not a fixed unit; the kernel has used 100
+/// ticks/sec in the `/proc` ABI for every distro in practice regardless of `CONFIG_HZ`, so
+/// this is safe to hardcode rather than pull in a `libc` dependency just for `sysconf`.
+#[cfg(target_os = "linux")]
+const CLOCK_TICKS_PER_SEC: u64 = 100;
+
+/// Reads this process's accumulated (utime, stime) in clock ticks from `/proc/[pid]/stat`.
+/// Field 2 (comm) is parenthesized and may itself contain spaces, so the split happens after
+/// its closing paren rather than by whitespace position.
+#[cfg(target_os = "linux")]
+fn read_proc_cpu_ticks(pid: u32) -> Option<(u64, u64)> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let after_comm = stat.rsplit_once(')')?.1;
+    let fields: Vec<&str> = after_comm.split_whitespace().collect();
+    let utime = fields.get(11)?.parse().ok()?;
+    let stime = fields.get(12)?.parse().ok()?;
+    Some((utime, stime))
+}
+
+/// Tracks cumulative CPU ticks between samples to derive a user/system percent split. On
+/// non-Linux targets there's no portable equivalent of `/proc/[pid]/stat`, so this degrades
+/// to always reporting `None` rather than fabricating a number.
+#[cfg(target_os = "linux")]
+struct CpuTimeTracker {
+    prev_ticks: Option<(u64, u64)>,
+    prev_instant: Instant,
+}
+
+#[cfg(target_os = "linux")]
+impl CpuTimeTracker {
+    fn new() -> Self {
+        Self {
+            prev_ticks: read_proc_cpu_ticks(std::process::id()),
+            prev_instant: Instant::now(),
+        }
+    }
+
+    /// Returns the (user%, system%) consumed since the previous call, or `None`/`None` if
+    /// ticks weren't readable or no time has passed yet.
+    fn sample(&mut self) -> (Option<f32>, Option<f32>) {
+        let now = Instant::now();
+        let elapsed_secs = now.duration_since(self.prev_instant).as_secs_f32();
+        self.prev_instant = now;
+        let ticks = read_proc_cpu_ticks(std::process::id());
+        let result = match (self.prev_ticks, ticks) {
+            (Some((prev_user, prev_sys)), Some((user, sys))) if elapsed_secs > 0.0 => {
+                let ticks_to_percent = |delta_ticks: u64| {
+                    100.0 * (delta_ticks as f32 / CLOCK_TICKS_PER_SEC as f32) / elapsed_secs
+                };
+                (
+                    Some(ticks_to_percent(user.saturating_sub(prev_user))),
+                    Some(ticks_to_percent(sys.saturating_sub(prev_sys))),
+                )
+            }
+            _ => (None, None),
+        };
+        self.prev_ticks = ticks;
+        result
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+struct CpuTimeTracker;
+
+#[cfg(not(target_os = "linux"))]
+impl CpuTimeTracker {
+    fn new() -> Self {
+        Self
+    }
+
+    fn sample(&mut self) -> (Option<f32>, Option<f32>) {
+        (None, None)
+    }
+}
+
+/// Samples this process's CPU usage and resident memory on a background thread until
+/// [`ResourceSampler::stop`] is called.
+pub struct ResourceSampler {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<Vec<Sample>>,
+}
+
+impl ResourceSampler {
+    pub fn start() -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let my_stop = stop.clone();
+        let handle = std::thread::spawn(move || {
+            let pid = sysinfo::Pid::from(std::process::id() as usize);
+            let mut system = sysinfo::System::new();
+            let mut cpu_times = CpuTimeTracker::new();
+            let mut samples = vec![];
+            while !my_stop.load(Ordering::Relaxed) {
+                system.refresh_process(pid);
+                if let Some(process) = system.process(pid) {
+                    let (cpu_user_percent, cpu_system_percent) = cpu_times.sample();
+                    samples.push(Sample {
+                        cpu_percent: process.cpu_usage(),
+                        cpu_user_percent,
+                        cpu_system_percent,
+                        memory_bytes: process.memory(),
+                    });
+                }
+                std::thread::sleep(SAMPLE_INTERVAL);
+            }
+            samples
+        });
+        Self { stop, handle }
+    }
+
+    /// Stops sampling and returns the collected timeline.
+    pub fn stop(self) -> Vec<Sample> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.join().unwrap()
+    }
+}
+
+/// Peak/mean CPU and memory across a timeline of [`Sample`]s.
+pub struct ResourceSummary {
+    pub peak_cpu_percent: f32,
+    pub mean_cpu_percent: f32,
+    /// `None` if no sample carried a user/system split (e.g. when not running on Linux);
+    /// otherwise computed over only the samples that did.
+    pub peak_cpu_user_percent: Option<f32>,
+    pub mean_cpu_user_percent: Option<f32>,
+    pub peak_cpu_system_percent: Option<f32>,
+    pub mean_cpu_system_percent: Option<f32>,
+    pub peak_memory_bytes: u64,
+    pub mean_memory_bytes: u64,
+    pub sample_count: usize,
+}
+
+fn peak_mean(values: &[f32]) -> Option<(f32, f32)> {
+    if values.is_empty() {
+        return None;
+    }
+    let peak = values.iter().copied().fold(0.0, f32::max);
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    Some((peak, mean))
+}
+
+impl ResourceSummary {
+    pub fn from_samples(samples: &[Sample]) -> Self {
+        if samples.is_empty() {
+            return Self {
+                peak_cpu_percent: 0.0,
+                mean_cpu_percent: 0.0,
+                peak_cpu_user_percent: None,
+                mean_cpu_user_percent: None,
+                peak_cpu_system_percent: None,
+                mean_cpu_system_percent: None,
+                peak_memory_bytes: 0,
+                mean_memory_bytes: 0,
+                sample_count: 0,
+            };
+        }
+        let user_samples: Vec<f32> = samples.iter().filter_map(|s| s.cpu_user_percent).collect();
+        let system_samples: Vec<f32> =
+            samples.iter().filter_map(|s| s.cpu_system_percent).collect();
+        let (peak_cpu_user_percent, mean_cpu_user_percent) = match peak_mean(&user_samples) {
+            Some((peak, mean)) => (Some(peak), Some(mean)),
+            None => (None, None),
+        };
+        let (peak_cpu_system_percent, mean_cpu_system_percent) = match peak_mean(&system_samples) {
+            Some((peak, mean)) => (Some(peak), Some(mean)),
+            None => (None, None),
+        };
+        Self {
+            peak_cpu_percent: samples.iter().map(|s| s.cpu_percent).fold(0.0, f32::max),
+            mean_cpu_percent: samples.iter().map(|s| s.cpu_percent).sum::<f32>()
+                / samples.len() as f32,
+            peak_cpu_user_percent,
+            mean_cpu_user_percent,
+            peak_cpu_system_percent,
+            mean_cpu_system_percent,
+            peak_memory_bytes: samples.iter().map(|s| s.memory_bytes).max().unwrap(),
+            mean_memory_bytes: samples.iter().map(|s| s.memory_bytes).sum::<u64>()
+                / samples.len() as u64,
+            sample_count: samples.len(),
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cpu: peak={:.1}% mean={:.1}%",
+            self.peak_cpu_percent, self.mean_cpu_percent,
+        )?;
+        if let (Some(peak_user), Some(mean_user), Some(peak_sys), Some(mean_sys)) = (
+            self.peak_cpu_user_percent,
+            self.mean_cpu_user_percent,
+            self.peak_cpu_system_percent,
+            self.mean_cpu_system_percent,
+        ) {
+            write!(
+                f,
+                " (user: peak={peak_user:.1}% mean={mean_user:.1}%, system: peak={peak_sys:.1}% mean={mean_sys:.1}%)",
+            )?;
+        }
+        write!(
+            f,
+            "   memory: peak={}MB mean={}MB   ({} samples)",
+            self.peak_memory_bytes / 1_000_000,
+            self.mean_memory_bytes / 1_000_000,
+            self.sample_count,
+        )
+    }
+}
@@ -0,0 +1,100 @@
+//! Stall watchdog for long-running or `--focus`-indefinite loops: a pathological backend can
+//! livelock with no diagnostic otherwise, leaving the process just hanging. Each worker
+//! publishes a heartbeat after every completed operation; a background thread scans for any
+//! worker whose last heartbeat is older than the configured timeout and aborts with
+//! diagnostics rather than letting the run hang silently.
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::unix_timestamp_nanos;
+
+const SCAN_INTERVAL: Duration = Duration::from_millis(200);
+
+struct Shared {
+    last_progress_nanos: Vec<AtomicU64>,
+    finished: Vec<AtomicBool>,
+    labels: Vec<String>,
+}
+
+pub struct Watchdog {
+    shared: Arc<Shared>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Watchdog {
+    /// `labels` names each worker (e.g. `"writer-0"`) for diagnostics, and its length fixes
+    /// the number of workers that must call [`Self::heartbeat`] or [`Self::finish`].
+    /// `map_size` is called only on breach, to report the map's size at the time of the stall.
+    pub fn start(
+        labels: Vec<String>,
+        timeout: Duration,
+        map_size: impl Fn() -> usize + Send + 'static,
+    ) -> Self {
+        let now = unix_timestamp_nanos() as u64;
+        let timeout_nanos = timeout.as_nanos() as u64;
+        let count = labels.len();
+        let shared = Arc::new(Shared {
+            last_progress_nanos: (0..count).map(|_| AtomicU64::new(now)).collect(),
+            finished: (0..count).map(|_| AtomicBool::new(false)).collect(),
+            labels,
+        });
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let scan_shared = shared.clone();
+        let scan_stop = stop.clone();
+        let handle = std::thread::spawn(move || {
+            while !scan_stop.load(Ordering::Relaxed) {
+                std::thread::sleep(SCAN_INTERVAL);
+                let now = unix_timestamp_nanos() as u64;
+                for worker in 0..scan_shared.labels.len() {
+                    if scan_shared.finished[worker].load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    let last_progress =
+                        scan_shared.last_progress_nanos[worker].load(Ordering::Relaxed);
+                    let stalled_nanos = now.saturating_sub(last_progress);
+                    if stalled_nanos < timeout_nanos {
+                        continue;
+                    }
+                    let stalled_secs = stalled_nanos as f64 / 1e9;
+                    eprintln!(
+                        "\nwatchdog: {} stalled for {stalled_secs:.1}s (timeout {timeout:?}); map size = {}",
+                        scan_shared.labels[worker],
+                        map_size(),
+                    );
+                    std::process::exit(1);
+                }
+            }
+        });
+
+        Self {
+            shared,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Records that `worker` completed an operation just now, pushing its deadline out.
+    /// A plain atomic store: no lock, no allocation, so this is safe to call on every
+    /// operation in the hot loop without perturbing the contention signal being measured.
+    pub fn heartbeat(&self, worker: usize) {
+        let now = unix_timestamp_nanos() as u64;
+        self.shared.last_progress_nanos[worker].store(now, Ordering::Relaxed);
+    }
+
+    /// Marks `worker` as done, so it stops being watched once it exits normally.
+    pub fn finish(&self, worker: usize) {
+        self.shared.finished[worker].store(true, Ordering::Relaxed);
+    }
+
+    /// Stops the watchdog thread. Call once every worker has called [`Self::finish`].
+    pub fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.join().unwrap();
+        }
+    }
+}